@@ -4,40 +4,102 @@ use diff_utils::{Comparison, DisplayOptions, PatchOptions};
 use regex::Regex;
 use serde::de::DeserializeOwned;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::SystemTime;
 use std::{collections::HashMap, io::Write};
 
-fn assert_section(entry: Entry, actual: String) -> Result<()> {
-    let mut new_snap_path: PathBuf = entry.entry.into();
-    let ext = format!("{}.new", entry.section_name);
+/// Number of units to run at once. Honors `SNAPSHOT_WORKERS`, falling back to
+/// the machine's available parallelism.
+fn worker_count() -> usize {
+    std::env::var("SNAPSHOT_WORKERS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        })
+}
+
+/// Per-unit timeout in milliseconds. Honors `SNAPSHOT_TIMEOUT_MS`, defaulting
+/// to the crate's historical whole-run timeout of 60s.
+fn per_test_timeout_ms() -> u32 {
+    std::env::var("SNAPSHOT_TIMEOUT_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60_000)
+}
+
+/// Honors `UPDATE_SNAPSHOTS=1`: instead of leaving a `.new` patch behind, a
+/// mismatch is rewritten straight into the originating `.snap` file.
+fn update_snapshots() -> bool {
+    matches!(std::env::var("UPDATE_SNAPSHOTS").as_deref(), Ok("1") | Ok("true"))
+}
+
+/// Monotonic counter folded into [`write_file_atomically`]'s temp file name so
+/// that concurrent writers to the same fixture (e.g. two `#[test]` functions
+/// in the same `.snap` file, run on separate threads by the default test
+/// harness) never share a temp path, even though they share a pid.
+static TMP_FILE_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+fn write_file_atomically(path: &Path, contents: &str) -> Result<()> {
+    let unique = TMP_FILE_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let tmp_path = path.with_file_name(format!(
+        "{}.tmp{}-{}",
+        path.file_name().unwrap().to_string_lossy(),
+        std::process::id(),
+        unique
+    ));
+    std::fs::write(&tmp_path, contents)
+        .with_context(|| format!("Failed to write temp file {}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to replace {} with updated snapshot", path.display()))?;
+    Ok(())
+}
+
+/// Builds the expected/actual line diff shared by [`assert_section`] (which
+/// also needs the `Comparison` to emit a `.new` patch) and [`review_one`]
+/// (which only needs the rendered text).
+fn diff_sections<'a>(unit: &Unit, expected: &'a str, actual: &'a str) -> Result<(Comparison<'a>, String)> {
+    let expected_lines = expected.lines().collect::<Vec<_>>();
+    let actual_lines = actual.lines().collect::<Vec<_>>();
+    let comparison = Comparison::new(&expected_lines, &actual_lines).compare()?;
+    let diff = comparison.display(DisplayOptions {
+        offset: unit.line,
+        ..Default::default()
+    });
+    Ok((comparison, diff))
+}
+
+fn assert_section(unit: &Unit, actual: String) -> Result<()> {
+    let mut new_snap_path: PathBuf = unit.path.clone();
+    let ext = format!("{}.new", unit.section_name);
     new_snap_path.set_extension(&ext);
 
-    let expected = format!("{}{}", entry.section, entry.last_line);
+    let expected = format!("{}{}", &unit.source[unit.from..unit.to], unit.last_line);
 
     if expected != actual {
-        let expected_lines = expected.lines().collect::<Vec<_>>();
-        let actual_lines = actual.lines().collect::<Vec<_>>();
-        let comparison = Comparison::new(&expected_lines, &actual_lines).compare()?;
+        let (comparison, diff) = diff_sections(unit, &expected, &actual)?;
         eprintln!(
             "\nFound mismatch in section [{}] in {}\n{}",
-            entry.section_name,
-            entry.entry.display(),
-            comparison.display(DisplayOptions {
-                offset: entry.line,
-                ..Default::default()
-            })
+            unit.section_name,
+            unit.path.display(),
+            diff
         );
 
+        if update_snapshots() {
+            splice_unit(unit, &actual)?;
+            return Ok(());
+        }
+
         std::fs::File::create(&new_snap_path).and_then(|mut file| {
-            let datetime: DateTime<Local> = entry.modified.into();
+            let datetime: DateTime<Local> = unit.modified.into();
             let dt = datetime.format("%F %T %z");
 
-            let entry_basename = entry.entry.file_name().unwrap().to_string_lossy();
+            let entry_basename = unit.path.file_name().unwrap().to_string_lossy();
             let snap_basename = new_snap_path.file_name().unwrap().to_string_lossy();
 
-            // writeln!(file, "```")?;
-            // writeln!(file, "{}", entry.input)?;
-            // writeln!(file, "```")?;
             write!(
                 file,
                 "{}",
@@ -46,12 +108,12 @@ fn assert_section(entry: Entry, actual: String) -> Result<()> {
                     &dt,
                     snap_basename,
                     &dt,
-                    PatchOptions { offset: entry.line }
+                    PatchOptions { offset: unit.line }
                 )
             )
         })?;
 
-        bail!("failed");
+        bail!("{}", diff);
     } else if new_snap_path.exists() {
         std::fs::remove_file(new_snap_path)?;
     }
@@ -71,11 +133,48 @@ struct Entry<'a> {
     section_name: &'a str,
     line: usize,
     section: &'a str,
-    entry: &'a Path,
+    from: usize,
+    to: usize,
     modified: SystemTime,
     last_line: &'a str,
 }
 
+/// Which fixture syntax a [`Unit`] was discovered from, since the two need
+/// slightly different `actual` framing around the same from/to splice region.
+enum UnitFormat {
+    /// `tests/**/*.snap`: `[section]` headers, section text includes its own header.
+    Ini,
+    /// `tests/**/*.md`: fenced code blocks, section text is just the block body.
+    Markdown,
+}
+
+/// An owned, dispatch-ready unit of work: everything needed to run one fixture
+/// file's section through `f` and compare/splice the result, without borrowing
+/// from the discovery pass so it can be handed to a worker thread.
+struct Unit {
+    path: PathBuf,
+    source: String,
+    from: usize,
+    to: usize,
+    section_name: String,
+    line: usize,
+    last_line: String,
+    modified: SystemTime,
+    inputs: HashMap<String, String>,
+    format: UnitFormat,
+}
+
+impl Unit {
+    fn render_actual(&self, output: &str) -> String {
+        match self.format {
+            UnitFormat::Ini => {
+                format!("[{}]\n{}\n\n{}", self.section_name, output, self.last_line)
+            }
+            UnitFormat::Markdown => format!("{}\n{}", output, self.last_line),
+        }
+    }
+}
+
 pub struct SnapshotInputs {
     inputs: HashMap<String, String>,
 }
@@ -99,163 +198,885 @@ impl SnapshotInputs {
 
 pub fn test_snapshots<F>(section_name: &'static str, f: F) -> Result<()>
 where
-    F: 'static + std::panic::RefUnwindSafe + Fn(&SnapshotInputs) -> String + Send,
+    F: 'static + std::panic::RefUnwindSafe + Fn(&SnapshotInputs) -> String + Send + Sync,
 {
-    const TIMEOUT: u32 = 60_000;
-    use pulse::{Signal, TimeoutError};
-    let (signal_start, pulse_start) = Signal::new();
-    let (signal_end, pulse_end) = Signal::new();
-
-    let guard = std::thread::spawn(move || {
-        pulse_start.pulse();
-        let result = test_snapshots_inner(section_name, f);
-        pulse_end.pulse();
-        result
-    });
+    test_snapshots_inner(section_name, f)
+}
+
+/// Async counterpart to [`test_snapshots`], for subjects-under-test that are
+/// themselves async: `f` returns a `Future<Output = String>` instead of a
+/// `String` directly. Each invocation is driven with `block_on` on a Tokio
+/// runtime — the currently-entered one if this function is called from inside
+/// e.g. a `#[tokio::test]`, or a fresh default runtime built just for this call
+/// otherwise. `inputs.get_json` and friends work unchanged from within the
+/// async body.
+pub fn test_snapshots_async<F, Fut>(section_name: &'static str, f: F) -> Result<()>
+where
+    F: 'static + std::panic::RefUnwindSafe + Fn(&SnapshotInputs) -> Fut + Send + Sync,
+    Fut: std::future::Future<Output = String>,
+{
+    let (_owned_runtime, handle) = tokio_handle()?;
+    test_snapshots_async_inner(section_name, f, handle)
+}
+
+/// Reuses the ambient Tokio runtime if this is already being called from
+/// within one, otherwise spins up a default multi-threaded runtime that lives
+/// for the duration of the call. The returned `Runtime` must be kept alive by
+/// the caller for as long as `handle` is in use.
+fn tokio_handle() -> Result<(Option<tokio::runtime::Runtime>, tokio::runtime::Handle)> {
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+        return Ok((None, handle));
+    }
+    let runtime =
+        tokio::runtime::Runtime::new().context("Failed to start a default Tokio runtime")?;
+    let handle = runtime.handle().clone();
+    Ok((Some(runtime), handle))
+}
 
-    signal_start.wait().unwrap();
-    match signal_end.wait_timeout_ms(TIMEOUT) {
-        Err(TimeoutError::Timeout) => {
-            bail!("Timed out");
+/// Watches `tests/**/*.snap`, `tests/**/*.md` and the crate's `src` tree, and
+/// re-runs the section-matching logic on every change, clearing the screen
+/// and printing a fresh pass/fail summary each time. Normally only fixture
+/// files whose `modified` time changed since the last pass are re-run, the
+/// rest reusing their last outcome; a `src` change can't be detected that
+/// way, so it forces every unit to re-run instead.
+pub fn watch_snapshots<F>(section_name: &'static str, f: F) -> Result<()>
+where
+    F: 'static + std::panic::RefUnwindSafe + Fn(&SnapshotInputs) -> String + Send + Sync,
+{
+    use notify::{RecursiveMode, Watcher};
+
+    let f = Arc::new(f);
+    let section_key = format!("expected.{}", section_name);
+    let root = std::env::current_dir()?;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(&root.join("tests"), RecursiveMode::Recursive)?;
+    let src_dir = root.join("src");
+    if src_dir.exists() {
+        watcher.watch(&src_dir, RecursiveMode::Recursive)?;
+    }
+
+    let mut last_modified: HashMap<PathBuf, SystemTime> = HashMap::new();
+    let mut last_success: HashMap<PathBuf, bool> = HashMap::new();
+
+    loop {
+        run_watch_pass(&section_key, &f, &mut last_modified, &mut last_success)?;
+
+        loop {
+            match rx.recv() {
+                Ok(Ok(event)) if is_relevant_change(&event) => {
+                    if !touches_fixture(&event) {
+                        // A `src/` change can't be detected via fixture mtimes
+                        // (nothing under tests/ changed), so force every unit
+                        // to re-run against the rebuilt code instead of
+                        // replaying last pass's cached results.
+                        last_modified.clear();
+                    }
+                    break;
+                }
+                Ok(_) => continue,
+                Err(e) => bail!("Watcher disconnected: {}", e),
+            }
         }
-        _ => (),
     }
+}
 
-    guard.join().unwrap()
+fn is_relevant_change(event: &notify::Event) -> bool {
+    event.paths.iter().any(|path| {
+        matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("snap") | Some("md") | Some("rs")
+        )
+    })
 }
 
-fn test_snapshots_inner<F>(section_name: &str, f: F) -> Result<()>
+fn touches_fixture(event: &notify::Event) -> bool {
+    event.paths.iter().any(|path| {
+        matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("snap") | Some("md")
+        )
+    })
+}
+
+fn clear_screen() {
+    eprint!("\x1B[2J\x1B[1;1H");
+}
+
+/// One watch iteration: discover units, skip re-running any whose file wasn't
+/// touched since the last pass (tracked via `last_modified`), and merge fresh
+/// results with the cached ones from `last_success` for the summary.
+fn run_watch_pass<F>(
+    section_key: &str,
+    f: &Arc<F>,
+    last_modified: &mut HashMap<PathBuf, SystemTime>,
+    last_success: &mut HashMap<PathBuf, bool>,
+) -> Result<()>
 where
-    F: std::panic::RefUnwindSafe + Fn(&SnapshotInputs) -> String,
+    F: 'static + std::panic::RefUnwindSafe + Fn(&SnapshotInputs) -> String + Send + Sync,
 {
-    struct CurrentSection<'a> {
-        from: usize,
-        from_inner: usize,
-        to: usize,
-        last_line: Option<(usize, usize)>,
-        line: usize,
-        name: &'a str,
-    }
-
-    impl<'a> CurrentSection<'a> {
-        fn into_entry(self, source: &'a str, entry: &'a PathBuf) -> Result<Entry<'a>> {
-            let (from, kind) = if self.name.starts_with("expected.") {
-                (self.from, EntryKind::Expected)
-            } else {
-                (self.from_inner, EntryKind::Input)
-            };
+    clear_screen();
 
-            let metadata = std::fs::metadata(&entry)?;
+    let (units, skipped) = discover_units(section_key)?;
+    let processed = units.len();
 
-            let last_line = match self.last_line {
-                Some((from, to)) => &source[from..to],
-                None => &source[self.to..self.to],
-            };
+    let (changed, unchanged): (Vec<Unit>, Vec<Unit>) = units
+        .into_iter()
+        .partition(|unit| last_modified.get(&unit.path) != Some(&unit.modified));
+
+    let mut successes = 0;
+    for unit in &unchanged {
+        let ok = *last_success.get(&unit.path).unwrap_or(&false);
+        eprint!("{}", if ok { "." } else { "F" });
+        if ok {
+            successes += 1;
+        }
+    }
+
+    for unit in &changed {
+        last_modified.insert(unit.path.clone(), unit.modified);
+    }
+
+    let reports = run_units(changed, Arc::clone(f), worker_count(), per_test_timeout_ms());
+    for report in reports {
+        let ok = report.is_success();
+        if ok {
+            successes += 1;
+        }
+        last_success.insert(report.path, ok);
+    }
+
+    eprintln!(
+        "\nProcessed {}: {}, Failed: {}, Skipped: {}",
+        section_key,
+        processed,
+        processed - successes,
+        skipped
+    );
+
+    Ok(())
+}
+
+/// Interactive review of mismatched sections, for triaging a large snapshot
+/// churn one unit at a time instead of updating everything at once.
+/// Unlike [`test_snapshots`], a mismatch doesn't bail the run: each failing
+/// unit is run one at a time, its diff is rendered, and the user is asked to
+/// accept (splice the new output into the `.snap`/`.md` file, same as
+/// `UPDATE_SNAPSHOTS`), skip, or open the fixture in `$EDITOR` before
+/// deciding. Matching units are still discovered and rendered the normal way,
+/// so this works over whichever fixtures currently produce a mismatch —
+/// including ones that already left a `.new` patch behind from a prior
+/// failing run.
+pub fn review_snapshots<F>(section_name: &'static str, f: F) -> Result<()>
+where
+    F: 'static + std::panic::RefUnwindSafe + Fn(&SnapshotInputs) -> String + Send + Sync,
+{
+    let section_key = format!("expected.{}", section_name);
+    let (units, skipped) = discover_units(&section_key)?;
+
+    let mut mismatches = 0;
+    let mut accepted = 0;
+    for unit in units {
+        if let Some(was_accepted) = review_one(unit, &f)? {
+            mismatches += 1;
+            if was_accepted {
+                accepted += 1;
+            }
+        }
+    }
+
+    eprintln!(
+        "\nReviewed {}: {} mismatched, {} accepted, {} skipped, {} had no matching section",
+        section_key,
+        mismatches,
+        accepted,
+        mismatches - accepted,
+        skipped
+    );
+
+    Ok(())
+}
+
+/// Diffs one unit against `f`'s output and, if it's a mismatch, renders the
+/// diff and loops on accept/skip/edit until the user picks accept or skip.
+/// Returns `None` if the unit already matches, `Some(accepted)` otherwise.
+///
+/// After every `[e]dit`, the fixture is re-read via [`reparse_unit`] and the
+/// diff recomputed from scratch: `$EDITOR` may have changed the file out from
+/// under the in-memory `Unit` captured at discovery time, and looping on the
+/// stale pre-edit diff/offsets would silently clobber whatever the user just
+/// typed if they then chose `[a]ccept`.
+fn review_one<F>(mut unit: Unit, f: &F) -> Result<Option<bool>>
+where
+    F: Fn(&SnapshotInputs) -> String,
+{
+    loop {
+        let inputs = SnapshotInputs {
+            inputs: unit.inputs.clone(),
+        };
+        let output = f(&inputs);
+        let actual = unit.render_actual(&output);
+        let expected = format!("{}{}", &unit.source[unit.from..unit.to], unit.last_line);
+        if expected == actual {
+            return Ok(None);
+        }
 
-            Ok(Entry {
-                kind,
-                entry,
-                section_name: self.name,
-                section: &source[from..self.to],
-                line: self.line,
-                last_line,
-                modified: metadata.modified()?,
+        let (_, diff) = diff_sections(&unit, &expected, &actual)?;
+
+        eprintln!(
+            "\n--- {} [{}] ---\n{}",
+            unit.path.display(),
+            unit.section_name,
+            diff
+        );
+        eprint!("Accept new output? [a]ccept/[s]kip/[e]dit: ");
+        std::io::stderr().flush().ok();
+
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        match answer.trim().to_ascii_lowercase().as_str() {
+            "a" | "accept" => {
+                splice_unit(&unit, &actual)?;
+                return Ok(Some(true));
+            }
+            "s" | "skip" | "" => return Ok(Some(false)),
+            "e" | "edit" => {
+                open_in_editor(&unit.path)?;
+                match reparse_unit(&unit.path, &unit.section_name)? {
+                    Some(fresh) => unit = fresh,
+                    None => {
+                        eprintln!(
+                            "Section [{}] no longer found in {} after edit, skipping",
+                            unit.section_name,
+                            unit.path.display()
+                        );
+                        return Ok(Some(false));
+                    }
+                }
+            }
+            other => eprintln!("Unrecognized choice: {:?}", other),
+        }
+    }
+}
+
+/// Rewrites `unit.path` with `actual` spliced in at `unit.from..unit.to`, then
+/// cleans up the stale `.new` patch if one exists. Shared by
+/// [`assert_section`]'s `UPDATE_SNAPSHOTS` branch and [`review_one`]'s
+/// `[a]ccept`.
+fn splice_unit(unit: &Unit, actual: &str) -> Result<()> {
+    let new_section = &actual[..actual.len() - unit.last_line.len()];
+    let mut updated = String::with_capacity(unit.source.len());
+    updated.push_str(&unit.source[..unit.from]);
+    updated.push_str(new_section);
+    updated.push_str(&unit.source[unit.to..]);
+    write_file_atomically(&unit.path, &updated)?;
+
+    let mut new_snap_path = unit.path.clone();
+    new_snap_path.set_extension(format!("{}.new", unit.section_name));
+    if new_snap_path.exists() {
+        std::fs::remove_file(new_snap_path)?;
+    }
+    Ok(())
+}
+
+fn open_in_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+    std::process::Command::new(editor)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to launch $EDITOR on {}", path.display()))?;
+    Ok(())
+}
+
+/// Outcome of running a single unit, rich enough to feed both the human
+/// summary and a machine reporter (TAP/JUnit): the diagnostic carries the
+/// mismatch diff for failures, or a short message for timeouts/panics.
+enum UnitStatus {
+    Success,
+    Failure(String),
+    Panic(String),
+    Timeout,
+}
+
+struct UnitReport {
+    path: PathBuf,
+    section_name: String,
+    status: UnitStatus,
+}
+
+impl UnitReport {
+    fn is_success(&self) -> bool {
+        matches!(self.status, UnitStatus::Success)
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Runs a chunk of units at a time (bounded by `workers`), each on its own thread
+/// with its own `timeout_ms` deadline and `catch_unwind`, so one hung or panicking
+/// `.snap` file is reported as a single failure instead of aborting the whole run.
+/// `produce` is the only thing that differs between [`run_units`] and
+/// [`run_units_async`]: calling `f(&inputs)` directly versus driving its
+/// future with `block_on`.
+fn run_units_with(
+    units: Vec<Unit>,
+    produce: Arc<dyn Fn(&SnapshotInputs) -> String + Send + Sync>,
+    workers: usize,
+    timeout_ms: u32,
+) -> Vec<UnitReport> {
+    use pulse::{Signal, TimeoutError};
+
+    let mut reports = Vec::with_capacity(units.len());
+    let mut units = units.into_iter();
+    loop {
+        let chunk: Vec<Unit> = (&mut units).take(workers.max(1)).collect();
+        if chunk.is_empty() {
+            break;
+        }
+
+        let jobs: Vec<_> = chunk
+            .into_iter()
+            .map(|unit| {
+                let produce = Arc::clone(&produce);
+                let (signal_start, pulse_start) = Signal::new();
+                let (signal_end, pulse_end) = Signal::new();
+                let path = unit.path.clone();
+                let section_name = unit.section_name.clone();
+                let handle = std::thread::spawn(move || {
+                    pulse_start.pulse();
+                    let inputs = SnapshotInputs {
+                        inputs: unit.inputs.clone(),
+                    };
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let output = produce(&inputs);
+                        let actual = unit.render_actual(&output);
+                        assert_section(&unit, actual)
+                    }));
+                    pulse_end.pulse();
+                    result
+                });
+                (path, section_name, handle, signal_start, signal_end)
             })
+            .collect();
+
+        for (path, section_name, handle, signal_start, signal_end) in jobs {
+            signal_start.wait().unwrap();
+            let status = match signal_end.wait_timeout_ms(timeout_ms) {
+                Err(TimeoutError::Timeout) => {
+                    eprintln!("{}: Timed out\n", path.display());
+                    UnitStatus::Timeout
+                }
+                _ => match handle.join() {
+                    Ok(Ok(Ok(()))) => {
+                        eprint!(".");
+                        UnitStatus::Success
+                    }
+                    Ok(Ok(Err(e))) => {
+                        eprintln!("{}: {:?}\n", path.display(), e);
+                        UnitStatus::Failure(format!("{:?}", e))
+                    }
+                    Ok(Err(payload)) => {
+                        let message = panic_message(payload.as_ref());
+                        eprintln!("{}: Thread panicked: {}\n", path.display(), message);
+                        UnitStatus::Panic(message)
+                    }
+                    Err(payload) => {
+                        let message = panic_message(payload.as_ref());
+                        eprintln!("{}: Worker thread panicked: {}\n", path.display(), message);
+                        UnitStatus::Panic(message)
+                    }
+                },
+            };
+            reports.push(UnitReport {
+                path,
+                section_name,
+                status,
+            });
         }
     }
 
+    reports
+}
+
+fn run_units<F>(units: Vec<Unit>, f: Arc<F>, workers: usize, timeout_ms: u32) -> Vec<UnitReport>
+where
+    F: 'static + std::panic::RefUnwindSafe + Fn(&SnapshotInputs) -> String + Send + Sync,
+{
+    run_units_with(units, f, workers, timeout_ms)
+}
+
+struct CurrentSection<'a> {
+    from: usize,
+    from_inner: usize,
+    to: usize,
+    last_line: Option<(usize, usize)>,
+    line: usize,
+    name: &'a str,
+}
+
+impl<'a> CurrentSection<'a> {
+    fn into_entry(self, source: &'a str, entry: &'a PathBuf) -> Result<Entry<'a>> {
+        let (from, kind) = if self.name.starts_with("expected.") {
+            (self.from, EntryKind::Expected)
+        } else {
+            (self.from_inner, EntryKind::Input)
+        };
+
+        let metadata = std::fs::metadata(&entry)?;
+
+        let last_line = match self.last_line {
+            Some((from, to)) => &source[from..to],
+            None => &source[self.to..self.to],
+        };
+
+        Ok(Entry {
+            kind,
+            section_name: self.name,
+            section: &source[from..self.to],
+            from,
+            to: self.to,
+            line: self.line,
+            last_line,
+            modified: metadata.modified()?,
+        })
+    }
+}
+
+/// Combines the two fixture discovery passes: `.snap` INI-style sections and
+/// `.md` fenced code blocks.
+fn discover_units(section_key: &str) -> Result<(Vec<Unit>, usize)> {
+    let (mut units, mut skipped) = discover_snap_units(section_key)?;
+    let (md_units, md_skipped) = discover_markdown_units(section_key)?;
+    units.extend(md_units);
+    skipped += md_skipped;
+    Ok((units, skipped))
+}
+
+/// Parses one `.snap` file's `[section]` headers and, if it defines the
+/// `expected.<section_key>` region, returns the [`Unit`] built from it.
+/// Split out of [`discover_snap_units`] so a single file can be re-parsed
+/// after it changes on disk (e.g. from `$EDITOR` during [`review_snapshots`])
+/// without re-globbing the whole `tests/**/*.snap` tree.
+fn parse_snap_file(entry: PathBuf, section_key: &str, section_regex: &Regex) -> Result<Option<Unit>> {
+    let entry_file = load_file(&entry)?;
+    let mut sections: HashMap<String, Entry> = HashMap::default();
+    let mut current_section: Option<CurrentSection> = None;
+    let input_len = entry_file.lines().count();
+    for (line_idx, line) in entry_file.lines().enumerate() {
+        if let Some(captures) = section_regex.captures(line) {
+            let offset = offset(&entry_file, line);
+            let len = line.len();
+
+            if let Some(mut current_section) = current_section.take() {
+                current_section.to = offset;
+                current_section.last_line = Some((offset, offset + len));
+                sections.insert(
+                    current_section.name.into(),
+                    current_section.into_entry(&entry_file, &entry)?,
+                );
+            }
+            let name = captures.get(1).unwrap().as_str();
+            current_section = Some(CurrentSection {
+                name,
+                from: offset,
+                from_inner: offset + len,
+                to: offset + len,
+                last_line: None,
+                line: input_len + line_idx,
+            });
+        }
+    }
+
+    if let Some(mut current_section) = current_section.take() {
+        current_section.to = entry_file.len();
+        sections.insert(
+            current_section.name.into(),
+            current_section.into_entry(&entry_file, &entry)?,
+        );
+    }
+
+    let (inputs, mut expected): (HashMap<_, _>, HashMap<_, _>) = sections
+        .into_iter()
+        .partition(|(_name, section)| matches!(section.kind, EntryKind::Input));
+
+    let Some(section) = expected.remove(section_key) else {
+        return Ok(None);
+    };
+
+    let inputs = inputs
+        .into_iter()
+        .map(|(k, v)| (k, v.section.to_string()))
+        .collect();
+
+    let from = section.from;
+    let to = section.to;
+    let line = section.line;
+    let modified = section.modified;
+    let section_name = section.section_name.to_string();
+    let last_line = section.last_line.to_string();
+
+    Ok(Some(Unit {
+        path: entry,
+        source: entry_file,
+        from,
+        to,
+        section_name,
+        line,
+        last_line,
+        modified,
+        inputs,
+        format: UnitFormat::Ini,
+    }))
+}
+
+/// Globs `tests/**/*.snap`, parses out `[section]` headers and splits them into
+/// inputs and the `expected.<section_key>` region, returning one [`Unit`] per
+/// file that defines that section plus a count of files that don't.
+fn discover_snap_units(section_key: &str) -> Result<(Vec<Unit>, usize)> {
     let section_regex = Regex::new(r"^\s*\[([[:alpha:]\.-_]+)\]\s*$")?;
     let path = std::env::current_dir()?;
-    let mut successes = 0;
-    let mut processed = 0;
     let mut skipped = 0;
+    let mut units = Vec::new();
     for entry in glob::glob(&format!("{}/tests/**/*.snap", path.display()))? {
         let entry = entry?;
-        let entry_file = load_file(&entry)?;
-        let mut sections: HashMap<String, Entry> = HashMap::default();
-        let mut current_section: Option<CurrentSection> = None;
-        let input_len = entry_file.lines().count();
-        for (line_idx, line) in entry_file.lines().enumerate() {
-            if let Some(captures) = section_regex.captures(line) {
-                let offset = offset(&entry_file, line);
-                let len = line.len();
-
-                if let Some(mut current_section) = current_section.take() {
-                    current_section.to = offset;
-                    current_section.last_line = Some((offset, offset + len));
-                    sections.insert(
-                        current_section.name.into(),
-                        current_section.into_entry(&entry_file, &entry)?,
-                    );
-                }
-                let name = captures.get(1).unwrap().as_str();
-                current_section = Some(CurrentSection {
-                    name,
-                    from: offset,
-                    from_inner: offset + len,
-                    to: offset + len,
-                    last_line: None,
-                    line: input_len + line_idx,
+        match parse_snap_file(entry, section_key, &section_regex)? {
+            Some(unit) => units.push(unit),
+            None => skipped += 1,
+        }
+    }
+
+    Ok((units, skipped))
+}
+
+/// Pure pass over one Markdown document's fenced code blocks, split out of
+/// [`discover_markdown_units`] so the CommonMark-walking logic can be
+/// exercised without touching the filesystem. Maps each `input.foo` /
+/// `expected.bar` info string to `(body_from, body_to, block_end)`.
+///
+/// For an empty block (no `Event::Text` between `Start`/`End`), the body
+/// range falls back to the position just past the opening fence's line
+/// rather than to the `End` event's range: per pulldown-cmark, a container
+/// event's range spans the *whole* block, so using it directly would point
+/// `body_from == body_to` at the start of the opening fence, and splicing
+/// new content there would land it before the fence instead of inside it.
+fn parse_markdown_sections(text: &str) -> HashMap<String, (usize, usize, usize)> {
+    use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+
+    let mut sections = HashMap::new();
+    let mut current_lang: Option<String> = None;
+    let mut body_range: Option<std::ops::Range<usize>> = None;
+    let mut empty_body_pos = 0;
+
+    for (event, range) in Parser::new(text).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                current_lang = Some(info.to_string());
+                body_range = None;
+                empty_body_pos = text[range.start..]
+                    .find('\n')
+                    .map(|i| range.start + i + 1)
+                    .unwrap_or(range.start);
+            }
+            Event::Text(_) if current_lang.is_some() => {
+                // A block body can arrive as several Text events (one per
+                // line), so widen the range to cover all of them.
+                body_range = Some(match body_range.take() {
+                    Some(prev) => prev.start..range.end,
+                    None => range,
                 });
             }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(name) = current_lang.take() {
+                    let body = body_range.take().unwrap_or(empty_body_pos..empty_body_pos);
+                    sections.insert(name, (body.start, body.end, range.end));
+                }
+            }
+            _ => {}
         }
+    }
+
+    sections
+}
+
+/// Parses one `.md` file's fenced code blocks and, if it defines the
+/// `expected.<section_key>` block, returns the [`Unit`] built from it. Split
+/// out of [`discover_markdown_units`] so a single file can be re-parsed after
+/// it changes on disk (e.g. from `$EDITOR` during [`review_snapshots`])
+/// without re-globbing the whole `tests/**/*.md` tree.
+fn parse_markdown_file(entry: PathBuf, section_key: &str) -> Result<Option<Unit>> {
+    let entry_file = load_file(&entry)?;
+    let modified = std::fs::metadata(&entry)?.modified()?;
+
+    let sections = parse_markdown_sections(&entry_file);
 
-        if let Some(mut current_section) = current_section.take() {
-            current_section.to = entry_file.len();
-            sections.insert(
-                current_section.name.into(),
-                current_section.into_entry(&entry_file, &entry)?,
-            );
+    let mut inputs = HashMap::new();
+    let mut expected_section = None;
+    for (name, bounds) in sections {
+        if name == section_key {
+            expected_section = Some(bounds);
+        } else if !name.starts_with("expected.") {
+            let (from, to, _) = bounds;
+            inputs.insert(name, entry_file[from..to].to_string());
         }
+    }
 
-        let section_name = format!("expected.{}", section_name);
-        let (inputs, mut expected): (HashMap<_, _>, HashMap<_, _>) = sections
-            .into_iter()
-            .partition(|(_name, section)| matches!(section.kind, EntryKind::Input));
-
-        if let Some(section) = expected.remove(&section_name) {
-            let inputs = inputs
-                .into_iter()
-                .map(|(k, v)| (k, v.section.into()))
-                .collect();
-            let inputs = SnapshotInputs { inputs };
-            let result = std::panic::catch_unwind(|| f(&inputs));
-            match result {
-                Ok(output) => {
-                    let actual = format!("[{}]\n{}\n\n{}", section_name, output, section.last_line);
-                    match assert_section(section, actual) {
-                        Ok(_) => {
-                            successes += 1;
-                            eprint!(".");
-                        }
-                        Err(e) => {
-                            eprintln!("{}: {:?}\n", entry.display(), e);
-                        }
-                    }
-                }
+    let Some((from, to, block_end)) = expected_section else {
+        return Ok(None);
+    };
 
-                Err(_) => {
-                    eprintln!("{}: Thread panicked\n", entry.display());
-                }
-            }
-            processed += 1;
-        } else {
-            skipped += 1;
+    let line = entry_file[..from].matches('\n').count();
+    let last_line = entry_file[to..block_end].to_string();
+    Ok(Some(Unit {
+        path: entry,
+        source: entry_file,
+        from,
+        to,
+        section_name: section_key.to_string(),
+        line,
+        last_line,
+        modified,
+        inputs,
+        format: UnitFormat::Markdown,
+    }))
+}
+
+/// Extracts sections from fenced code blocks in `tests/**/*.md`: an info
+/// string like `input.foo` or `expected.bar` names the section, the block
+/// body is its content, and the block's byte range lets mismatches splice
+/// back into the exact fence in update mode.
+fn discover_markdown_units(section_key: &str) -> Result<(Vec<Unit>, usize)> {
+    let path = std::env::current_dir()?;
+    let mut skipped = 0;
+    let mut units = Vec::new();
+
+    for entry in glob::glob(&format!("{}/tests/**/*.md", path.display()))? {
+        let entry = entry?;
+        match parse_markdown_file(entry, section_key)? {
+            Some(unit) => units.push(unit),
+            None => skipped += 1,
         }
     }
+
+    Ok((units, skipped))
+}
+
+/// Re-parses a single [`Unit`] by path and section key, dispatching to the
+/// `.snap`/`.md` parser by extension. Used by [`review_one`] to refresh the
+/// in-memory `Unit` after `$EDITOR` may have changed the file on disk, so a
+/// subsequent `[a]ccept` doesn't splice against stale offsets.
+fn reparse_unit(path: &Path, section_key: &str) -> Result<Option<Unit>> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("md") => parse_markdown_file(path.to_path_buf(), section_key),
+        _ => {
+            let section_regex = Regex::new(r"^\s*\[([[:alpha:]\.-_]+)\]\s*$")?;
+            parse_snap_file(path.to_path_buf(), section_key, &section_regex)
+        }
+    }
+}
+
+fn test_snapshots_inner<F>(section_name: &str, f: F) -> Result<()>
+where
+    F: 'static + std::panic::RefUnwindSafe + Fn(&SnapshotInputs) -> String + Send + Sync,
+{
+    let section_key = format!("expected.{}", section_name);
+    let (units, skipped) = discover_units(&section_key)?;
+
+    let processed = units.len();
+    let reports = run_units(units, Arc::new(f), worker_count(), per_test_timeout_ms());
+    let successes = reports.iter().filter(|r| r.is_success()).count();
     eprintln!(
         "\nProcessed {}: {}, Failed: {}, Skipped: {}",
-        section_name,
+        section_key,
         processed,
         processed - successes,
         skipped
     );
+    emit_machine_report(&section_key, &reports)?;
     if successes != processed {
         bail!("Some tests failed");
     }
     Ok(())
 }
 
+fn test_snapshots_async_inner<F, Fut>(
+    section_name: &str,
+    f: F,
+    handle: tokio::runtime::Handle,
+) -> Result<()>
+where
+    F: 'static + std::panic::RefUnwindSafe + Fn(&SnapshotInputs) -> Fut + Send + Sync,
+    Fut: std::future::Future<Output = String>,
+{
+    let section_key = format!("expected.{}", section_name);
+    let (units, skipped) = discover_units(&section_key)?;
+
+    let processed = units.len();
+    let reports = run_units_async(
+        units,
+        Arc::new(f),
+        handle,
+        worker_count(),
+        per_test_timeout_ms(),
+    );
+    let successes = reports.iter().filter(|r| r.is_success()).count();
+    eprintln!(
+        "\nProcessed {}: {}, Failed: {}, Skipped: {}",
+        section_key,
+        processed,
+        processed - successes,
+        skipped
+    );
+    emit_machine_report(&section_key, &reports)?;
+    if successes != processed {
+        bail!("Some tests failed");
+    }
+    Ok(())
+}
+
+/// Async twin of [`run_units`]: same chunking, per-unit timeout and
+/// `catch_unwind` isolation via [`run_units_with`], except each unit's
+/// `f(&inputs)` future is driven to completion with `handle.block_on` rather
+/// than called directly.
+fn run_units_async<F, Fut>(
+    units: Vec<Unit>,
+    f: Arc<F>,
+    handle: tokio::runtime::Handle,
+    workers: usize,
+    timeout_ms: u32,
+) -> Vec<UnitReport>
+where
+    F: 'static + std::panic::RefUnwindSafe + Fn(&SnapshotInputs) -> Fut + Send + Sync,
+    Fut: std::future::Future<Output = String>,
+{
+    let produce: Arc<dyn Fn(&SnapshotInputs) -> String + Send + Sync> =
+        Arc::new(move |inputs: &SnapshotInputs| handle.block_on(f(inputs)));
+    run_units_with(units, produce, workers, timeout_ms)
+}
+
+/// Emits a CI-ingestible report alongside the human summary, selected via
+/// `SNAPSHOT_REPORTER` (`tap` or `junit`) and written to `SNAPSHOT_REPORT_PATH`
+/// (defaulting to `snapshot-report.<ext>`). A no-op if `SNAPSHOT_REPORTER` is unset.
+fn emit_machine_report(suite_name: &str, reports: &[UnitReport]) -> Result<()> {
+    let reporter = match std::env::var("SNAPSHOT_REPORTER") {
+        Ok(v) => v,
+        Err(_) => return Ok(()),
+    };
+
+    let (default_path, body) = match reporter.to_ascii_lowercase().as_str() {
+        "tap" => ("snapshot-report.tap", render_tap(reports)),
+        "junit" => ("snapshot-report.xml", render_junit(suite_name, reports)),
+        other => bail!("Unknown SNAPSHOT_REPORTER: {}", other),
+    };
+
+    let report_path =
+        std::env::var("SNAPSHOT_REPORT_PATH").unwrap_or_else(|_| default_path.to_string());
+    std::fs::write(&report_path, body)
+        .with_context(|| format!("Failed to write machine report to {}", report_path))
+}
+
+fn unit_test_name(report: &UnitReport) -> String {
+    format!("{}::{}", report.path.display(), report.section_name)
+}
+
+fn render_tap(reports: &[UnitReport]) -> String {
+    let mut out = String::from("TAP version 13\n");
+    out.push_str(&format!("1..{}\n", reports.len()));
+
+    for (idx, report) in reports.iter().enumerate() {
+        let number = idx + 1;
+        let name = unit_test_name(report);
+        match &report.status {
+            UnitStatus::Success => out.push_str(&format!("ok {} - {}\n", number, name)),
+            UnitStatus::Failure(diagnostic) => {
+                out.push_str(&format!("not ok {} - {}\n", number, name));
+                out.push_str("  ---\n");
+                out.push_str("  message: mismatch\n  diff: |\n");
+                for line in diagnostic.lines() {
+                    out.push_str(&format!("    {}\n", line));
+                }
+                out.push_str("  ...\n");
+            }
+            UnitStatus::Timeout => {
+                out.push_str(&format!("not ok {} - {}\n", number, name));
+                out.push_str("  ---\n  message: timed out\n  ...\n");
+            }
+            UnitStatus::Panic(message) => {
+                out.push_str(&format!("not ok {} - {}\n", number, name));
+                out.push_str(&format!("  ---\n  message: {}\n  ...\n", message));
+            }
+        }
+    }
+
+    out
+}
+
+fn render_junit(suite_name: &str, reports: &[UnitReport]) -> String {
+    let failures = reports
+        .iter()
+        .filter(|r| matches!(r.status, UnitStatus::Failure(_)))
+        .count();
+    let errors = reports
+        .iter()
+        .filter(|r| matches!(r.status, UnitStatus::Timeout | UnitStatus::Panic(_)))
+        .count();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+        xml_escape(suite_name),
+        reports.len(),
+        failures,
+        errors
+    ));
+
+    for report in reports {
+        out.push_str(&format!(
+            "  <testcase classname=\"{}\" name=\"{}\">\n",
+            xml_escape(&report.path.display().to_string()),
+            xml_escape(&report.section_name)
+        ));
+        match &report.status {
+            UnitStatus::Success => {}
+            UnitStatus::Failure(diagnostic) => {
+                out.push_str(&format!(
+                    "    <failure message=\"Snapshot mismatch\">{}</failure>\n",
+                    xml_escape(diagnostic)
+                ));
+            }
+            UnitStatus::Timeout => {
+                out.push_str("    <error message=\"Timed out\"></error>\n");
+            }
+            UnitStatus::Panic(message) => {
+                out.push_str(&format!(
+                    "    <error message=\"Panicked\">{}</error>\n",
+                    xml_escape(message)
+                ));
+            }
+        }
+        out.push_str("  </testcase>\n");
+    }
+
+    out.push_str("</testsuite>\n");
+    out
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
 fn offset(parent: &str, child: &str) -> usize {
     let parent_ptr = parent.as_ptr() as usize;
     let child_ptr = child.as_ptr() as usize;
@@ -266,3 +1087,154 @@ fn load_file(entry: &Path) -> Result<String> {
     let s = std::fs::read_to_string(entry)?;
     Ok(s)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_message_downcasts_str_and_string_payloads() {
+        let str_payload: Box<dyn std::any::Any + Send> = Box::new("boom");
+        assert_eq!(panic_message(str_payload.as_ref()), "boom");
+
+        let string_payload: Box<dyn std::any::Any + Send> = Box::new("boom".to_string());
+        assert_eq!(panic_message(string_payload.as_ref()), "boom");
+    }
+
+    #[test]
+    fn panic_message_falls_back_for_other_payloads() {
+        let other_payload: Box<dyn std::any::Any + Send> = Box::new(42_i32);
+        assert_eq!(panic_message(other_payload.as_ref()), "unknown panic");
+    }
+
+    fn notify_event(path: &str) -> notify::Event {
+        notify::Event::new(notify::EventKind::Modify(notify::event::ModifyKind::Any))
+            .add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn is_relevant_change_matches_snap_md_and_rs_only() {
+        assert!(is_relevant_change(&notify_event("tests/a.snap")));
+        assert!(is_relevant_change(&notify_event("tests/a.md")));
+        assert!(is_relevant_change(&notify_event("src/lib.rs")));
+        assert!(!is_relevant_change(&notify_event("tests/a.snap.new")));
+        assert!(!is_relevant_change(&notify_event("README.txt")));
+    }
+
+    #[test]
+    fn touches_fixture_is_false_for_source_only_changes() {
+        assert!(touches_fixture(&notify_event("tests/a.snap")));
+        assert!(touches_fixture(&notify_event("tests/a.md")));
+        assert!(!touches_fixture(&notify_event("src/lib.rs")));
+    }
+
+    #[test]
+    fn xml_escape_escapes_all_five_entities() {
+        assert_eq!(
+            xml_escape(r#"<a & b> "c" 'd'"#),
+            "&lt;a &amp; b&gt; &quot;c&quot; &apos;d&apos;"
+        );
+    }
+
+    fn report(status: UnitStatus) -> UnitReport {
+        UnitReport {
+            path: PathBuf::from("tests/a.snap"),
+            section_name: "expected.foo".to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn render_tap_emits_ok_and_diagnostic_block_for_failures() {
+        let reports = vec![report(UnitStatus::Success), report(UnitStatus::Timeout)];
+        let tap = render_tap(&reports);
+        assert!(tap.starts_with("TAP version 13\n1..2\n"));
+        assert!(tap.contains("ok 1 - tests/a.snap::expected.foo\n"));
+        assert!(tap.contains("not ok 2 - tests/a.snap::expected.foo\n"));
+        assert!(tap.contains("message: timed out"));
+    }
+
+    #[test]
+    fn render_junit_counts_failures_and_errors_separately_and_escapes_xml() {
+        let reports = vec![
+            report(UnitStatus::Success),
+            report(UnitStatus::Failure("<diff>".to_string())),
+            report(UnitStatus::Panic("boom".to_string())),
+        ];
+        let xml = render_junit("expected.foo", &reports);
+        assert!(xml.contains(r#"tests="3" failures="1" errors="1""#));
+        assert!(xml.contains("&lt;diff&gt;"));
+    }
+
+    #[test]
+    fn parse_markdown_sections_splices_empty_block_inside_the_fence() {
+        let text = "```expected.empty\n```\n";
+        let sections = parse_markdown_sections(text);
+        let (from, to, _) = sections["expected.empty"];
+        assert_eq!(from, to, "empty block should have a zero-width body");
+
+        let opening_line_end = text.find('\n').unwrap() + 1;
+        assert_eq!(
+            from, opening_line_end,
+            "empty body should sit just past the opening fence's line, not at its start"
+        );
+    }
+
+    #[test]
+    fn parse_markdown_sections_extracts_nonempty_block_body() {
+        let text = "```expected.has_body\nhello\n```\n";
+        let sections = parse_markdown_sections(text);
+        let (from, to, _) = sections["expected.has_body"];
+        assert!(from < to);
+        assert_eq!(text[from..to].trim_end(), "hello");
+    }
+
+    #[test]
+    fn tokio_handle_builds_its_own_runtime_outside_one() {
+        let (owned, _handle) = tokio_handle().unwrap();
+        assert!(owned.is_some());
+    }
+
+    #[test]
+    fn tokio_handle_reuses_the_ambient_runtime() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let owned_is_none = runtime.block_on(async { tokio_handle().unwrap().0.is_none() });
+        assert!(owned_is_none);
+    }
+
+    /// [`splice_unit`] shares its offset-based splice arithmetic with
+    /// [`assert_section`] under `UPDATE_SNAPSHOTS`; this exercises it directly
+    /// against a real file so a regression in that arithmetic shows up as a
+    /// wrong byte range rather than only in an interactive session.
+    #[test]
+    fn splice_unit_replaces_only_the_section_byte_range() {
+        let path = std::env::temp_dir().join(format!(
+            "snapshot_runner_splice_unit_test_{}_{}.snap",
+            std::process::id(),
+            TMP_FILE_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        ));
+        let original = "[input.x]\nfoo\n\n[expected.y]\nold\n\n";
+        std::fs::write(&path, original).unwrap();
+
+        let from = original.find("old\n").unwrap();
+        let to = from + "old\n".len();
+        let unit = Unit {
+            path: path.clone(),
+            source: original.to_string(),
+            from,
+            to,
+            section_name: "expected.y".to_string(),
+            line: 0,
+            last_line: String::new(),
+            modified: std::fs::metadata(&path).unwrap().modified().unwrap(),
+            inputs: HashMap::new(),
+            format: UnitFormat::Ini,
+        };
+
+        splice_unit(&unit, "new\n").unwrap();
+
+        let updated = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(updated, "[input.x]\nfoo\n\n[expected.y]\nnew\n\n");
+    }
+}